@@ -0,0 +1,360 @@
+//! A self-contained assembler and disassembler for the CHIP-8 instruction set.
+//!
+//! This lets users inspect and author ROMs without a hex editor: `disassemble`
+//! turns raw ROM bytes into mnemonic lines, and `assemble` turns mnemonic
+//! source back into bytes, so a ROM can be round-tripped through
+//! assemble -> run -> disassemble.
+
+use failure::Error;
+use std::collections::HashMap;
+
+/// Disassemble a ROM image into one mnemonic line per 2-byte instruction.
+///
+/// Unknown or malformed opcodes are rendered as a `DW 0x....` (define word)
+/// line rather than aborting the whole disassembly, since raw ROM bytes may
+/// legitimately contain data the decoder doesn't recognise as code.
+pub fn disassemble(rom: &[u8]) -> Vec<String> {
+    rom.chunks(2)
+        .map(|chunk| {
+            let opcode = if chunk.len() == 2 {
+                ((chunk[0] as u16) << 8) | chunk[1] as u16
+            } else {
+                (chunk[0] as u16) << 8
+            };
+            disassemble_opcode(opcode)
+        })
+        .collect()
+}
+
+fn disassemble_opcode(opcode: u16) -> String {
+    let x = ((opcode & 0x0F00) >> 8) as usize;
+    let y = ((opcode & 0x00F0) >> 4) as usize;
+    let n = opcode & 0x000F;
+    let nn = opcode & 0x00FF;
+    let nnn = opcode & 0x0FFF;
+
+    match opcode & 0xF000 {
+        0x0000 => match opcode {
+            0x00E0 => "CLS".to_string(),
+            0x00EE => "RET".to_string(),
+            _ => format!("DW 0x{:04X}", opcode),
+        },
+        0x1000 => format!("JP 0x{:03X}", nnn),
+        0x2000 => format!("CALL 0x{:03X}", nnn),
+        0x3000 => format!("SE V{:X}, {}", x, nn),
+        0x4000 => format!("SNE V{:X}, {}", x, nn),
+        0x5000 if n == 0 => format!("SE V{:X}, V{:X}", x, y),
+        0x6000 => format!("LD V{:X}, {}", x, nn),
+        0x7000 => format!("ADD V{:X}, {}", x, nn),
+        0x8000 => match n {
+            0x0 => format!("LD V{:X}, V{:X}", x, y),
+            0x1 => format!("OR V{:X}, V{:X}", x, y),
+            0x2 => format!("AND V{:X}, V{:X}", x, y),
+            0x3 => format!("XOR V{:X}, V{:X}", x, y),
+            0x4 => format!("ADD V{:X}, V{:X}", x, y),
+            0x5 => format!("SUB V{:X}, V{:X}", x, y),
+            0x6 => format!("SHR V{:X}", x),
+            0x7 => format!("SUBN V{:X}, V{:X}", x, y),
+            0xE => format!("SHL V{:X}", x),
+            _ => format!("DW 0x{:04X}", opcode),
+        },
+        0x9000 if n == 0 => format!("SNE V{:X}, V{:X}", x, y),
+        0xA000 => format!("LD I, 0x{:03X}", nnn),
+        0xB000 => format!("JP V0, 0x{:03X}", nnn),
+        0xC000 => format!("RND V{:X}, {}", x, nn),
+        0xD000 => format!("DRW V{:X}, V{:X}, {}", x, y, n),
+        0xE000 => match nn {
+            0x9E => format!("SKP V{:X}", x),
+            0xA1 => format!("SKNP V{:X}", x),
+            _ => format!("DW 0x{:04X}", opcode),
+        },
+        0xF000 => match nn {
+            0x07 => format!("LD V{:X}, DT", x),
+            0x0A => format!("LD V{:X}, K", x),
+            0x15 => format!("LD DT, V{:X}", x),
+            0x18 => format!("LD ST, V{:X}", x),
+            0x1E => format!("ADD I, V{:X}", x),
+            0x29 => format!("LD F, V{:X}", x),
+            0x33 => format!("LD B, V{:X}", x),
+            0x55 => format!("LD [I], V{:X}", x),
+            0x65 => format!("LD V{:X}, [I]", x),
+            _ => format!("DW 0x{:04X}", opcode),
+        },
+        _ => format!("DW 0x{:04X}", opcode),
+    }
+}
+
+/// Assemble CHIP-8 mnemonic source into a raw, big-endian-encoded ROM image.
+///
+/// Labels are defined with a trailing colon (`loop:`) on their own line and
+/// referenced by name wherever an address operand is expected. Addresses are
+/// resolved as if the program were loaded at `0x200`, matching where `boot`
+/// places it in memory.
+pub fn assemble(source: &str) -> Result<Vec<u8>, Error> {
+    const PROGRAM_START_ADDRESS: u16 = 0x200;
+
+    let lines: Vec<(usize, &str)> = source
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, strip_comment(line).trim_end()))
+        .filter(|(_, line)| !line.trim_start().is_empty())
+        .collect();
+
+    // First pass: resolve every label to the address of the instruction that follows it.
+    let mut labels = HashMap::new();
+    let mut address = PROGRAM_START_ADDRESS;
+    for (line_no, line) in &lines {
+        if let Some(label) = line.strip_suffix(':') {
+            if labels.insert(label.trim().to_string(), address).is_some() {
+                let column = line.find(|c: char| !c.is_whitespace()).unwrap_or(0) + 1;
+                bail!("{}:{}: duplicate label '{}'", line_no, column, label.trim());
+            }
+        } else {
+            address += 2;
+        }
+    }
+
+    // Second pass: encode every instruction line, resolving label references.
+    let mut program = Vec::new();
+    for (line_no, line) in &lines {
+        if line.ends_with(':') {
+            continue;
+        }
+        let opcode = encode_line(*line_no, line, &labels)?;
+        program.push((opcode >> 8) as u8);
+        program.push((opcode & 0x00FF) as u8);
+    }
+
+    Ok(program)
+}
+
+fn strip_comment(line: &str) -> &str {
+    match line.find(';') {
+        Some(index) => &line[..index],
+        None => line,
+    }
+}
+
+fn encode_line(line_no: usize, line: &str, labels: &HashMap<String, u16>) -> Result<u16, Error> {
+    let column = line.find(|c: char| !c.is_whitespace()).unwrap_or(0) + 1;
+    let tokens: Vec<&str> = line
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|tok| !tok.is_empty())
+        .collect();
+
+    let mnemonic = tokens.get(0).copied().unwrap_or("");
+    let operands = &tokens[tokens.len().min(1)..];
+    let operand = |i: usize| -> Result<&str, Error> {
+        operands.get(i).copied().ok_or_else(|| {
+            format_err!("{}:{}: '{}' expects more operands", line_no, column, mnemonic)
+        })
+    };
+    let is_register_operand = |i: usize| operands.get(i).map_or(false, |tok| is_register(tok));
+
+    let opcode = match mnemonic.to_uppercase().as_str() {
+        "CLS" => 0x00E0,
+        "RET" => 0x00EE,
+        "JP" if operands.len() == 1 => 0x1000 | resolve_addr(line_no, column, operand(0)?, labels)?,
+        "JP" if operands.get(0).map_or(false, |op| op.eq_ignore_ascii_case("V0")) => {
+            0xB000 | resolve_addr(line_no, column, operand(1)?, labels)?
+        }
+        "CALL" => 0x2000 | resolve_addr(line_no, column, operand(0)?, labels)?,
+        "SE" if is_register_operand(1) => {
+            0x5000 | (parse_register(line_no, column, operand(0)?)? << 8)
+                | (parse_register(line_no, column, operand(1)?)? << 4)
+        }
+        "SE" => {
+            0x3000 | (parse_register(line_no, column, operand(0)?)? << 8)
+                | parse_byte(line_no, column, operand(1)?)? as u16
+        }
+        "SNE" if is_register_operand(1) => {
+            0x9000 | (parse_register(line_no, column, operand(0)?)? << 8)
+                | (parse_register(line_no, column, operand(1)?)? << 4)
+        }
+        "SNE" => {
+            0x4000 | (parse_register(line_no, column, operand(0)?)? << 8)
+                | parse_byte(line_no, column, operand(1)?)? as u16
+        }
+        "OR" => 0x8001 | (parse_register(line_no, column, operand(0)?)? << 8) | (parse_register(line_no, column, operand(1)?)? << 4),
+        "AND" => 0x8002 | (parse_register(line_no, column, operand(0)?)? << 8) | (parse_register(line_no, column, operand(1)?)? << 4),
+        "XOR" => 0x8003 | (parse_register(line_no, column, operand(0)?)? << 8) | (parse_register(line_no, column, operand(1)?)? << 4),
+        "SUB" => 0x8005 | (parse_register(line_no, column, operand(0)?)? << 8) | (parse_register(line_no, column, operand(1)?)? << 4),
+        "SHR" => 0x8006 | (parse_register(line_no, column, operand(0)?)? << 8),
+        "SUBN" => 0x8007 | (parse_register(line_no, column, operand(0)?)? << 8) | (parse_register(line_no, column, operand(1)?)? << 4),
+        "SHL" => 0x800E | (parse_register(line_no, column, operand(0)?)? << 8),
+        "ADD" if operands.get(0).map_or(false, |op| op.eq_ignore_ascii_case("I")) => {
+            0xF01E | (parse_register(line_no, column, operand(1)?)? << 8)
+        }
+        "ADD" if is_register_operand(1) => {
+            0x8004 | (parse_register(line_no, column, operand(0)?)? << 8) | (parse_register(line_no, column, operand(1)?)? << 4)
+        }
+        "ADD" => {
+            0x7000 | (parse_register(line_no, column, operand(0)?)? << 8)
+                | parse_byte(line_no, column, operand(1)?)? as u16
+        }
+        "RND" => {
+            0xC000 | (parse_register(line_no, column, operand(0)?)? << 8)
+                | parse_byte(line_no, column, operand(1)?)? as u16
+        }
+        "DRW" => {
+            0xD000
+                | (parse_register(line_no, column, operand(0)?)? << 8)
+                | (parse_register(line_no, column, operand(1)?)? << 4)
+                | parse_nibble(line_no, column, operand(2)?)? as u16
+        }
+        "SKP" => 0xE09E | (parse_register(line_no, column, operand(0)?)? << 8),
+        "SKNP" => 0xE0A1 | (parse_register(line_no, column, operand(0)?)? << 8),
+        "LD" => return encode_ld(line_no, column, operands, labels),
+        _ => bail!("{}:{}: unknown mnemonic '{}'", line_no, column, mnemonic),
+    };
+
+    Ok(opcode)
+}
+
+fn encode_ld(
+    line_no: usize,
+    column: usize,
+    operands: &[&str],
+    labels: &HashMap<String, u16>,
+) -> Result<u16, Error> {
+    if operands.len() != 2 {
+        bail!("{}:{}: LD expects two operands", line_no, column);
+    }
+    let (dst, src) = (operands[0], operands[1]);
+
+    let opcode = if dst.eq_ignore_ascii_case("I") {
+        0xA000 | resolve_addr(line_no, column, src, labels)?
+    } else if dst.eq_ignore_ascii_case("DT") {
+        0xF015 | (parse_register(line_no, column, src)? << 8)
+    } else if dst.eq_ignore_ascii_case("ST") {
+        0xF018 | (parse_register(line_no, column, src)? << 8)
+    } else if dst.eq_ignore_ascii_case("[I]") {
+        0xF055 | (parse_register(line_no, column, src)? << 8)
+    } else if src.eq_ignore_ascii_case("[I]") {
+        0xF065 | (parse_register(line_no, column, dst)? << 8)
+    } else if src.eq_ignore_ascii_case("DT") {
+        0xF007 | (parse_register(line_no, column, dst)? << 8)
+    } else if src.eq_ignore_ascii_case("K") {
+        0xF00A | (parse_register(line_no, column, dst)? << 8)
+    } else if dst.eq_ignore_ascii_case("F") {
+        0xF029 | (parse_register(line_no, column, src)? << 8)
+    } else if dst.eq_ignore_ascii_case("B") {
+        0xF033 | (parse_register(line_no, column, src)? << 8)
+    } else if is_register(src) {
+        0x8000 | (parse_register(line_no, column, dst)? << 8) | (parse_register(line_no, column, src)? << 4)
+    } else {
+        0x6000 | (parse_register(line_no, column, dst)? << 8) | parse_byte(line_no, column, src)? as u16
+    };
+
+    Ok(opcode)
+}
+
+fn is_register(token: &str) -> bool {
+    parse_register_str(token).is_some()
+}
+
+fn parse_register_str(token: &str) -> Option<u16> {
+    if token.len() == 2 && token.to_uppercase().starts_with('V') {
+        u16::from_str_radix(&token[1..], 16).ok().filter(|&v| v <= 0xF)
+    } else {
+        None
+    }
+}
+
+fn parse_register(line_no: usize, column: usize, token: &str) -> Result<u16, Error> {
+    parse_register_str(token)
+        .ok_or_else(|| format_err!("{}:{}: expected a register, found '{}'", line_no, column, token))
+}
+
+fn parse_number(token: &str) -> Option<u16> {
+    if let Some(hex) = token.strip_prefix("0x").or_else(|| token.strip_prefix("0X")) {
+        u16::from_str_radix(hex, 16).ok()
+    } else {
+        token.parse::<u16>().ok()
+    }
+}
+
+fn parse_byte(line_no: usize, column: usize, token: &str) -> Result<u8, Error> {
+    match parse_number(token) {
+        Some(value) if value <= 0xFF => Ok(value as u8),
+        _ => bail!("{}:{}: expected a byte literal, found '{}'", line_no, column, token),
+    }
+}
+
+fn parse_nibble(line_no: usize, column: usize, token: &str) -> Result<u8, Error> {
+    match parse_number(token) {
+        Some(value) if value <= 0xF => Ok(value as u8),
+        _ => bail!("{}:{}: expected a nibble literal, found '{}'", line_no, column, token),
+    }
+}
+
+fn resolve_addr(
+    line_no: usize,
+    column: usize,
+    token: &str,
+    labels: &HashMap<String, u16>,
+) -> Result<u16, Error> {
+    if let Some(value) = parse_number(token) {
+        if value > 0x0FFF {
+            bail!("{}:{}: address '{}' out of 12-bit range", line_no, column, token);
+        }
+        return Ok(value);
+    }
+    labels
+        .get(token)
+        .copied()
+        .ok_or_else(|| format_err!("{}:{}: undefined label '{}'", line_no, column, token))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disassembles_known_mnemonics() {
+        let rom = [0x00, 0xE0, 0x00, 0xEE, 0x1A, 0xBC, 0x60, 0x0A];
+        assert_eq!(
+            disassemble(&rom),
+            vec!["CLS", "RET", "JP 0xABC", "LD V0, 10"]
+        );
+    }
+
+    #[test]
+    fn disassembles_unknown_opcode_as_data_word() {
+        assert_eq!(disassemble(&[0xFF, 0xFF]), vec!["DW 0xFFFF"]);
+    }
+
+    #[test]
+    fn assembles_simple_program() {
+        let program = assemble("LD V0, 10\nADD V0, 1\nJP 0x200\n").unwrap();
+        assert_eq!(program, vec![0x60, 0x0A, 0x70, 0x01, 0x12, 0x00]);
+    }
+
+    #[test]
+    fn assembles_labels_relative_to_program_start() {
+        let program = assemble("loop:\n  JP loop\n").unwrap();
+        assert_eq!(program, vec![0x12, 0x00]);
+    }
+
+    #[test]
+    fn roundtrips_assemble_and_disassemble() {
+        let source = "start:\n  LD V0, 10\n  ADD V0, 1\n  JP start\n";
+        let bytes = assemble(source).unwrap();
+        assert_eq!(
+            disassemble(&bytes),
+            vec!["LD V0, 10", "ADD V0, 1", "JP 0x200"]
+        );
+    }
+
+    #[test]
+    fn reports_line_and_column_for_unknown_mnemonic() {
+        let err = assemble("NOPE V0\n").unwrap_err();
+        assert_eq!(err.to_string(), "1:1: unknown mnemonic 'NOPE'");
+    }
+
+    #[test]
+    fn reports_undefined_label() {
+        let err = assemble("JP missing\n").unwrap_err();
+        assert_eq!(err.to_string(), "1:1: undefined label 'missing'");
+    }
+}
@@ -1,7 +1,49 @@
 
+#[cfg(feature = "native")]
+use crate::frontend::{Frontend, SdlFrontend};
+#[cfg(feature = "native")]
+use crate::rng::ThreadRngSource;
+use crate::rng::{RngSource, SeededRngSource};
 use failure::Error;
+#[cfg(feature = "native")]
 use std::fs;
+#[cfg(feature = "native")]
 use std::path::Path;
+#[cfg(feature = "native")]
+use std::thread;
+#[cfg(feature = "native")]
+use std::time::{Duration, Instant};
+
+/// Integer scale factor applied to the 64x32 logical display by the default frontend.
+#[cfg(feature = "native")]
+const DEFAULT_DISPLAY_SCALE: u32 = 10;
+
+/// Default number of opcodes executed per second, independent of the fixed 60 Hz timers.
+const DEFAULT_CPU_HZ: u32 = 540;
+
+/// Timers always tick at this rate, regardless of `cpu_hz`.
+const TIMER_HZ: u32 = 60;
+
+/// The standard CHIP-8 hex font, 4x5 pixels per glyph (0-F), 5 bytes each.
+/// Conventionally loaded at the very start of memory so FX29 can address it as `Vx * 5`.
+const FONT_SET: [u8; 80] = [
+    0xF0, 0x90, 0x90, 0x90, 0xF0, // 0
+    0x20, 0x60, 0x20, 0x20, 0x70, // 1
+    0xF0, 0x10, 0xF0, 0x80, 0xF0, // 2
+    0xF0, 0x10, 0xF0, 0x10, 0xF0, // 3
+    0x90, 0x90, 0xF0, 0x10, 0x10, // 4
+    0xF0, 0x80, 0xF0, 0x10, 0xF0, // 5
+    0xF0, 0x80, 0xF0, 0x90, 0xF0, // 6
+    0xF0, 0x10, 0x20, 0x40, 0x40, // 7
+    0xF0, 0x90, 0xF0, 0x90, 0xF0, // 8
+    0xF0, 0x90, 0xF0, 0x10, 0xF0, // 9
+    0xF0, 0x90, 0xF0, 0x90, 0x90, // A
+    0xE0, 0x90, 0xE0, 0x90, 0xE0, // B
+    0xF0, 0x80, 0x80, 0x80, 0xF0, // C
+    0xE0, 0x90, 0x90, 0x90, 0xE0, // D
+    0xF0, 0x80, 0xF0, 0x80, 0xF0, // E
+    0xF0, 0x80, 0xF0, 0x80, 0x80, // F
+];
 
 /// Memory size
 const MEMORY_MAX : usize = 4096;
@@ -24,9 +66,6 @@ const PROGRAM_START_ADDRESS : u16 = 0x200;
 /// Register index used as carry
 const REGISTER_CARRY_FLAX_INDEX : usize = 0xF;
 
-/// Maximum value a register can hold
-const REGISTER_VALUE_MAX : u8 = 0xFF;
-
 pub struct Chip8 {
 
     /// stores the current opcode
@@ -47,8 +86,8 @@ pub struct Chip8 {
     /// The 16th register is used  for the ‘carry flag’. 
     V: [u8; REGISTERS_MAX],
 
-    /// Index register I 
-    index_register : u8,
+    /// Index register I
+    index_register : u16,
 
     /// program counter (pc) which can have a value from 0x000 to 0xFFF
     pc : u16,
@@ -81,16 +120,27 @@ pub struct Chip8 {
     /// Stack Pointer
     sp : u16,
 
-    /// Finally, the Chip 8 has a HEX based keypad (0x0-0xF), 
+    /// Finally, the Chip 8 has a HEX based keypad (0x0-0xF),
     /// Keypad array to store the current state of the key.
-    keypad : [u8;KEYPAD_MAX]
+    keypad : [u8;KEYPAD_MAX],
+
+    /// Rendering/input backend. `None` until `setup_gfx` installs one.
+    #[cfg(feature = "native")]
+    frontend : Option<Box<dyn Frontend>>,
+
+    /// How many opcodes `run` executes per second. Tunable via `set_cpu_hz`.
+    cpu_hz : u32,
+
+    /// Byte source backing CXNN. Thread-random by default under `native`, a fixed
+    /// seed in headless builds; seeded via `with_seed`.
+    rng : Box<dyn RngSource>
 
 }
 
 
 impl Chip8 {
     pub fn new() -> Chip8 {
-        Chip8 {
+        let mut chip8 = Chip8 {
             opcode : 0,
             memory : [0;MEMORY_MAX],
             V: [0;REGISTERS_MAX],
@@ -102,78 +152,147 @@ impl Chip8 {
             sound_timer : 0,
             stack : [0;STACK_MAX],
             sp : 0,
-            keypad: [0;KEYPAD_MAX]
-        }
+            keypad: [0;KEYPAD_MAX],
+            #[cfg(feature = "native")]
+            frontend: None,
+            cpu_hz: DEFAULT_CPU_HZ,
+            // Headless builds have no OS entropy source wired up (see rng.rs),
+            // so CXNN draws from a fixed seed until the host calls `with_seed`.
+            #[cfg(feature = "native")]
+            rng: Box::new(ThreadRngSource::new()),
+            #[cfg(not(feature = "native"))]
+            rng: Box::new(SeededRngSource::new(0)),
+        };
+        // Every consumer (native or headless) needs the hex font in memory for
+        // FX29/DXYN, so load it here rather than behind the native-only `boot` path.
+        chip8.load_fontset_in_memory();
+        chip8
     }
 
-    fn setup_gfx(&mut self) {
+    /// Builds a `Chip8` whose CXNN byte stream is seeded and therefore reproducible,
+    /// so a whole ROM run can be replayed deterministically for golden-trace tests.
+    pub fn with_seed(seed: u64) -> Chip8 {
+        let mut chip8 = Chip8::new();
+        chip8.rng = Box::new(SeededRngSource::new(seed));
+        chip8
+    }
 
+    /// Sets how many opcodes `run` executes per second. Timers keep ticking at a
+    /// fixed 60 Hz regardless of this value.
+    pub fn set_cpu_hz(&mut self, cpu_hz: u32) {
+        self.cpu_hz = cpu_hz;
     }
 
-    fn setup_input(&mut self) {
+    #[cfg(feature = "native")]
+    fn setup_gfx(&mut self) -> Result<(), Error> {
+        self.frontend = Some(Box::new(SdlFrontend::new(DEFAULT_DISPLAY_SCALE)?));
+        Ok(())
+    }
 
+    #[cfg(feature = "native")]
+    fn setup_input(&mut self) {
+        // Input is polled from the same SDL event pump `setup_gfx` created.
     }
 
+    #[cfg(feature = "native")]
     fn initialize(&mut self) {
         self.pc             =  PROGRAM_START_ADDRESS;   // Program counter starts at 0x200
-        self.opcode         = 0;                        // Reset current opcode	
+        self.opcode         = 0;                        // Reset current opcode
         self.index_register = 0;                        // Reset index register
         self.sp             = 0;                        // Reset stack pointer
-        
+
         // Clear display
-        unimplemented!();
+        self.gfx = [0; GFX_MEMORY_MAX];
+        self.draw_flag = false;
 
         // Clear stack
-        unimplemented!();
+        self.stack = [0; STACK_MAX];
 
         // Clear registers V0-VF
-        unimplemented!();
-        
+        self.V = [0; REGISTERS_MAX];
+
         // Clear memory
-        unimplemented!();
+        self.memory = [0; MEMORY_MAX];
 
         // Load fontset
         self.load_fontset_in_memory();
-    
+
         // Reset timers
-        unimplemented!();
+        self.delay_timer = 0;
+        self.sound_timer = 0;
     }
 
     fn load_fontset_in_memory(&mut self) {
-        /*
-        for(int i = 0; i < 80; ++i)
-            memory[i] = chip8_fontset[i];		
-        */
-        unimplemented!();
+        self.memory[..FONT_SET.len()].copy_from_slice(&FONT_SET);
     }
+
+    /// Copy a ROM image into memory starting at `PROGRAM_START_ADDRESS`.
+    ///
+    /// Unlike `load_program`, this takes the ROM bytes directly rather than a
+    /// filesystem path, so it works under `wasm32` (or any host without
+    /// `std::fs`) where a JS/wasm-bindgen wrapper has already fetched the ROM
+    /// into memory.
+    pub fn load_program_bytes(&mut self, rom: &[u8]) {
+        for (i, byte) in rom.iter().enumerate() {
+            self.memory[PROGRAM_START_ADDRESS as usize + i] = *byte;
+        }
+    }
+
+    #[cfg(feature = "native")]
     fn load_program(&mut self, rom_filename: &str) -> Result<(), Error> {
-    
         // Create a path to the desired file
         let path = Path::new(rom_filename);
-        
+
         // load program
         let buffer = fs::read(&path)?;
 
-        // write program in CHIP8 memory
-        for (i, byte) in buffer.iter().enumerate() {
-            self.memory[ PROGRAM_START_ADDRESS as usize + i as usize ] = *byte;
-        }
-        
+        self.load_program_bytes(&buffer);
+
         Ok(())
     }
+
     /// Boot the CHIP8 System
+    #[cfg(feature = "native")]
     pub fn boot(&mut self, rom_filename : &str) -> Result<(), Error> {
         // Set up render system and register input callbacks
-        self.setup_gfx();
+        self.setup_gfx()?;
         self.setup_input();
- 
-        // Initialize the Chip8 system and load the program into the memory  
+
+        // Initialize the Chip8 system and load the program into the memory
         self.initialize();
         self.load_program(rom_filename)?;
- 
+
         Ok(())
     }
 
+    /// Direct accessor for the 64x32 pixel buffer (one byte per pixel, 0 or 1).
+    ///
+    /// A headless host (e.g. a wasm-bindgen wrapper) reads this after `tick`
+    /// reports a redraw and blits it onto its own canvas, since there is no
+    /// `Frontend` to do that for it outside the `native` feature.
+    pub fn frame_buffer(&self) -> &[u8] {
+        &self.gfx
+    }
+
+    /// Run a single instruction and report whether it set `draw_flag`.
+    ///
+    /// This is the wasm-friendly replacement for `run`'s frame loop: the host
+    /// drives its own `requestAnimationFrame`/timer cadence and is expected to
+    /// call `handle_timers` itself at 60 Hz, same as the native loop does.
+    pub fn tick(&mut self) -> bool {
+        self.emulate_cycle();
+        let did_draw = self.draw_flag;
+        self.draw_flag = false;
+        did_draw
+    }
+
+    /// Set or clear a single key on the 16-entry keypad (`key` is 0x0-0xF).
+    pub fn set_key(&mut self, key: u8, pressed: bool) {
+        if let Some(slot) = self.keypad.get_mut(key as usize) {
+            *slot = pressed as u8;
+        }
+    }
+
     /// Fetch opcode from memory using pc
     fn fetch_opcode(&self) -> u16 {
         ((self.memory[self.pc as usize] as u16) << 8)  | (self.memory[(self.pc + 1) as usize]) as u16
@@ -199,6 +318,11 @@ impl Chip8 {
         (self.opcode & (0x0FFF as u16))
     }
 
+    /// extract lowest byte of current opcode, used as an 8-bit immediate
+    fn get_op_nn(&self) -> u8 {
+        (self.opcode & 0x00FF) as u8
+    }
+
     /// 4 higher bits of current opcode
     fn get_op_major(&self) -> u16 {
         self.opcode & 0xF000
@@ -209,112 +333,718 @@ impl Chip8 {
         self.opcode & (0x000F as u16)
     }
 
+    /// Skip the next instruction if `cond` holds, otherwise move to the next one
+    fn skip_if(&mut self, cond: bool) {
+        self.pc += if cond { 4 } else { 2 };
+    }
+
     pub fn emulate_cycle(&mut self) {
         // Fetch opcode
         self.opcode = self.fetch_opcode();
 
-        // Decode opcode
-        match self.get_op_major() {    
-            
-             0xA000 => {
-               // ANNN: Sets I to the address NNN
-                // Execute opcode
-                let operand = self.get_op_nnn() as u8; // remaining 12 bits contains address
-                self.index_register = operand;
-                self.pc += 2;  
-             },
-             0x0000 => { // 0x00E0 and 0x00EE both start with 0x0
-                 match self.get_op_lower() {
-                     0x0000 => {
-                         // 0x00E0: Clears the screen 
-                         unimplemented!();
-                     },
-                     0x000E => { // 0x00EE: Returns from subroutine
-                        unimplemented!();
-                     },
-                     _ => {
+        // Decode and execute opcode
+        match self.get_op_major() {
+            0x0000 => {
+                match self.get_op_lower() {
+                    0x0000 => {
+                        // 00E0: Clears the screen
+                        self.gfx = [0; GFX_MEMORY_MAX];
+                        self.draw_flag = true;
+                        self.pc += 2;
+                    },
+                    0x000E => {
+                        // 00EE: Returns from subroutine
+                        self.sp -= 1;
+                        self.pc = self.stack[self.sp as usize];
+                        self.pc += 2;
+                    },
+                    _ => {
                         println!("Unknown opcode: {:?}", self.opcode);
                         panic!();
                     }
-
-                 }
-             },
-             0x2000 => { //0x2NNN
+                }
+            },
+            0x1000 => {
+                // 1NNN: Jumps to address NNN
+                self.pc = self.get_op_nnn();
+            },
+            0x2000 => {
+                // 2NNN: Calls subroutine at NNN
                 self.stack[self.sp as usize] = self.pc;
                 self.sp += 1;
-                self.pc = self.opcode & 0x0FFF;
-             },
-             0x0004 => { //0x8XY4
-                let reg_x = self.get_op_x();
-                let reg_y = self.get_op_y();
-
-                if self.V[reg_y] > (REGISTER_VALUE_MAX - self.V[reg_x]) {
-                    self.carry_flag(true); //carry
-                } else {
-                    self.carry_flag(false);
+                self.pc = self.get_op_nnn();
+            },
+            0x3000 => {
+                // 3XNN: Skips the next instruction if Vx == NN
+                let x = self.get_op_x();
+                let nn = self.get_op_nn();
+                self.skip_if(self.V[x] == nn);
+            },
+            0x4000 => {
+                // 4XNN: Skips the next instruction if Vx != NN
+                let x = self.get_op_x();
+                let nn = self.get_op_nn();
+                self.skip_if(self.V[x] != nn);
+            },
+            0x5000 => {
+                // 5XY0: Skips the next instruction if Vx == Vy
+                let x = self.get_op_x();
+                let y = self.get_op_y();
+                self.skip_if(self.V[x] == self.V[y]);
+            },
+            0x6000 => {
+                // 6XNN: Sets Vx to NN
+                let x = self.get_op_x();
+                self.V[x] = self.get_op_nn();
+                self.pc += 2;
+            },
+            0x7000 => {
+                // 7XNN: Adds NN to Vx (no carry flag set)
+                let x = self.get_op_x();
+                let nn = self.get_op_nn();
+                self.V[x] = self.V[x].wrapping_add(nn);
+                self.pc += 2;
+            },
+            0x8000 => {
+                let x = self.get_op_x();
+                let y = self.get_op_y();
+                match self.get_op_lower() {
+                    0x0000 => self.V[x] = self.V[y], // 8XY0: Vx = Vy
+                    0x0001 => self.V[x] |= self.V[y], // 8XY1: Vx = Vx OR Vy
+                    0x0002 => self.V[x] &= self.V[y], // 8XY2: Vx = Vx AND Vy
+                    0x0003 => self.V[x] ^= self.V[y], // 8XY3: Vx = Vx XOR Vy
+                    0x0004 => {
+                        // 8XY4: Vx += Vy, VF = carry
+                        let (result, carry) = self.V[x].overflowing_add(self.V[y]);
+                        self.V[x] = result;
+                        self.carry_flag(carry);
+                    },
+                    0x0005 => {
+                        // 8XY5: Vx -= Vy, VF = NOT borrow
+                        let (result, borrow) = self.V[x].overflowing_sub(self.V[y]);
+                        self.V[x] = result;
+                        self.carry_flag(!borrow);
+                    },
+                    0x0006 => {
+                        // 8XY6: Vx >>= 1, VF = shifted-out bit
+                        let shifted_out = self.V[x] & 0x1;
+                        self.V[x] >>= 1;
+                        self.carry_flag(shifted_out == 1);
+                    },
+                    0x0007 => {
+                        // 8XY7: Vx = Vy - Vx, VF = NOT borrow
+                        let (result, borrow) = self.V[y].overflowing_sub(self.V[x]);
+                        self.V[x] = result;
+                        self.carry_flag(!borrow);
+                    },
+                    0x000E => {
+                        // 8XYE: Vx <<= 1, VF = shifted-out bit
+                        let shifted_out = (self.V[x] >> 7) & 0x1;
+                        self.V[x] <<= 1;
+                        self.carry_flag(shifted_out == 1);
+                    },
+                    _ => {
+                        println!("Unknown opcode: {:?}", self.opcode);
+                        panic!();
+                    }
                 }
-                self.V[reg_x] += self.V[reg_y];
-                self.pc += 2;          
+                self.pc += 2;
             },
-            0x0033 => { //0xFX33
-                let i_reg = self.index_register;
-                let reg_x = ((self.opcode & 0x0F00) >> 8) as usize;
-                self.memory[i_reg as usize]     = self.V[reg_x] / 100;
-                self.memory[(i_reg + 1) as usize] = (self.V[reg_x] / 10) % 10;
-                self.memory[(i_reg + 2) as usize] = (self.V[reg_x] % 100) % 10;
+            0x9000 => {
+                // 9XY0: Skips the next instruction if Vx != Vy
+                let x = self.get_op_x();
+                let y = self.get_op_y();
+                self.skip_if(self.V[x] != self.V[y]);
+            },
+            0xA000 => {
+                // ANNN: Sets I to the address NNN
+                self.index_register = self.get_op_nnn();
                 self.pc += 2;
-            }
-    
-             // More opcodes //
-        
-            // not handled
-             _ => {
+            },
+            0xB000 => {
+                // BNNN: Jumps to the address NNN + V0
+                self.pc = self.get_op_nnn() + self.V[0] as u16;
+            },
+            0xC000 => {
+                // CXNN: Vx = random byte AND NN
+                let x = self.get_op_x();
+                let nn = self.get_op_nn();
+                self.V[x] = self.rng.next_u8() & nn;
+                self.pc += 2;
+            },
+            0xD000 => {
+                // DXYN: Draws a sprite at (Vx, Vy) with width 8 and height N
+                let x = self.V[self.get_op_x()] as usize % 64;
+                let y = self.V[self.get_op_y()] as usize % 32;
+                let height = self.get_op_lower();
+
+                let mut collision = false;
+                for row in 0..height {
+                    let sprite_byte = self.memory[self.index_register as usize + row as usize];
+                    for col in 0..8 {
+                        if sprite_byte & (0x80 >> col) == 0 {
+                            continue;
+                        }
+                        let pixel_x = (x + col) % 64;
+                        let pixel_y = (y + row as usize) % 32;
+                        let pixel = &mut self.gfx[pixel_y * 64 + pixel_x];
+                        if *pixel == 1 {
+                            collision = true;
+                        }
+                        *pixel ^= 1;
+                    }
+                }
+                self.carry_flag(collision);
+                self.draw_flag = true;
+                self.pc += 2;
+            },
+            0xE000 => {
+                let x = self.get_op_x();
+                match self.opcode & 0x00FF {
+                    0x009E => {
+                        // EX9E: Skips the next instruction if key Vx is pressed
+                        let key = self.V[x] as usize;
+                        self.skip_if(self.keypad[key] != 0);
+                    },
+                    0x00A1 => {
+                        // EXA1: Skips the next instruction if key Vx is not pressed
+                        let key = self.V[x] as usize;
+                        self.skip_if(self.keypad[key] == 0);
+                    },
+                    _ => {
+                        println!("Unknown opcode: {:?}", self.opcode);
+                        panic!();
+                    }
+                }
+            },
+            0xF000 => {
+                let x = self.get_op_x();
+                match self.opcode & 0x00FF {
+                    0x0007 => {
+                        // FX07: Vx = delay timer
+                        self.V[x] = self.delay_timer;
+                        self.pc += 2;
+                    },
+                    0x000A => {
+                        // FX0A: Blocks until a key is pressed, then stores it in Vx
+                        match self.keypad.iter().position(|&pressed| pressed != 0) {
+                            Some(key) => {
+                                self.V[x] = key as u8;
+                                self.pc += 2;
+                            },
+                            None => {} // no key pressed yet, retry this instruction
+                        }
+                    },
+                    0x0015 => {
+                        // FX15: delay timer = Vx
+                        self.delay_timer = self.V[x];
+                        self.pc += 2;
+                    },
+                    0x0018 => {
+                        // FX18: sound timer = Vx
+                        self.sound_timer = self.V[x];
+                        self.pc += 2;
+                    },
+                    0x001E => {
+                        // FX1E: I += Vx, wrapped back into the 12-bit address space
+                        self.index_register = (self.index_register + self.V[x] as u16) & 0x0FFF;
+                        self.pc += 2;
+                    },
+                    0x0029 => {
+                        // FX29: I = address of the font sprite for digit Vx
+                        self.index_register = self.V[x] as u16 * 5;
+                        self.pc += 2;
+                    },
+                    0x0033 => {
+                        // FX33: Stores the BCD representation of Vx at I, I+1, I+2
+                        let i = self.index_register as usize;
+                        self.memory[i] = self.V[x] / 100;
+                        self.memory[i + 1] = (self.V[x] / 10) % 10;
+                        self.memory[i + 2] = self.V[x] % 10;
+                        self.pc += 2;
+                    },
+                    0x0055 => {
+                        // FX55: Stores V0..=Vx in memory starting at I
+                        let i = self.index_register as usize;
+                        for reg in 0..=x {
+                            self.memory[i + reg] = self.V[reg];
+                        }
+                        self.pc += 2;
+                    },
+                    0x0065 => {
+                        // FX65: Reads V0..=Vx from memory starting at I
+                        let i = self.index_register as usize;
+                        for reg in 0..=x {
+                            self.V[reg] = self.memory[i + reg];
+                        }
+                        self.pc += 2;
+                    },
+                    _ => {
+                        println!("Unknown opcode: {:?}", self.opcode);
+                        panic!();
+                    }
+                }
+            },
+            _ => {
                 println!("Unknown opcode: {:?}", self.opcode);
                 panic!();
-             }
-            
-        }  
-        
-        // Update timers
+            }
+        }
+    }
+
+    /// Ticks the delay and sound timers down towards zero at a fixed 60 Hz,
+    /// independent of how many opcodes `emulate_cycle` has run. Call once per frame.
+    pub fn handle_timers(&mut self) {
         if self.delay_timer > 0 {
             self.delay_timer -= 1;
         }
-            
-        if(self.sound_timer > 0)
-        {
+
+        if self.sound_timer > 0 {
             if self.sound_timer == 1 {
-                 println!("BEEP!");
+                self.beep();
             }
             self.sound_timer -= 1;
-        }  
+        }
+    }
+
+    /// Sound the CHIP-8 beep. Under `native` this goes through the installed
+    /// `Frontend`. Without `native` there is no audio backend, so this does
+    /// nothing.
+    #[cfg(feature = "native")]
+    fn beep(&mut self) {
+        match self.frontend.as_mut() {
+            Some(frontend) => frontend.beep(),
+            None => println!("BEEP!"),
+        }
     }
 
+    #[cfg(not(feature = "native"))]
+    fn beep(&mut self) {}
+
+    #[cfg(feature = "native")]
     pub fn draw_graphics(&mut self) {
-        unimplemented!();
+        if let Some(frontend) = self.frontend.as_mut() {
+            frontend.present(&self.gfx);
+        }
+        self.draw_flag = false;
     }
 
-    pub fn set_keys(&mut self) -> Result<(), Error> {
-        unimplemented!();
-        Ok(())
+    /// Pump input events. Returns `false` once the user has asked to quit.
+    #[cfg(feature = "native")]
+    pub fn set_keys(&mut self) -> Result<bool, Error> {
+        match self.frontend.as_mut() {
+            Some(frontend) => Ok(frontend.poll_input(&mut self.keypad)),
+            None => Ok(true),
+        }
     }
 
+    /// Runs the emulation loop, executing `cpu_hz` opcodes per second while
+    /// ticking the timers at a fixed 60 Hz, tracked against real time.
+    #[cfg(feature = "native")]
     pub fn run(&mut self) -> Result<(), Error> {
-        // Emulation loop
-        loop
-        {
-            // Emulate one cycle
-            self.emulate_cycle();
-        
-            // If the draw flag is set, update the screen
-            if self.draw_flag {
-                self.draw_graphics();
+        let frame_duration = Duration::from_secs_f64(1.0 / TIMER_HZ as f64);
+        let opcodes_per_frame = (self.cpu_hz / TIMER_HZ).max(1);
+
+        let mut last_frame = Instant::now();
+        let mut accumulator = Duration::from_secs(0);
+
+        loop {
+            let now = Instant::now();
+            accumulator += now - last_frame;
+            last_frame = now;
+
+            while accumulator >= frame_duration {
+                for _ in 0..opcodes_per_frame {
+                    self.emulate_cycle();
+                }
+                self.handle_timers();
+
+                if self.draw_flag {
+                    self.draw_graphics();
+                }
+
+                // Store key press state (Press and Release); stop on a quit event
+                if !self.set_keys()? {
+                    return Ok(());
+                }
+
+                accumulator -= frame_duration;
             }
-            
-        
-            // Store key press state (Press and Release)
-            self.set_keys()?;	
-        } 
 
-        Ok(())
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a Chip8 with `pc` pointed at PROGRAM_START_ADDRESS and the given
+    /// opcode loaded there, ready for a single `emulate_cycle`.
+    fn with_opcode(opcode: u16) -> Chip8 {
+        let mut chip8 = Chip8::new();
+        chip8.pc = PROGRAM_START_ADDRESS;
+        chip8.sp = 0;
+        let pc = chip8.pc as usize;
+        chip8.memory[pc] = (opcode >> 8) as u8;
+        chip8.memory[pc + 1] = (opcode & 0x00FF) as u8;
+        chip8
+    }
+
+    #[test]
+    fn op_00e0_clears_the_screen() {
+        let mut chip8 = with_opcode(0x00E0);
+        chip8.gfx[42] = 1;
+        chip8.emulate_cycle();
+        assert!(chip8.gfx.iter().all(|&pixel| pixel == 0));
+        assert!(chip8.draw_flag);
+    }
+
+    #[test]
+    fn op_00ee_returns_from_subroutine() {
+        let mut chip8 = with_opcode(0x00EE);
+        chip8.stack[0] = 0x300;
+        chip8.sp = 1;
+        chip8.emulate_cycle();
+        assert_eq!(chip8.sp, 0);
+        assert_eq!(chip8.pc, 0x302);
+    }
+
+    #[test]
+    fn op_1nnn_jumps_to_address() {
+        let mut chip8 = with_opcode(0x1ABC);
+        chip8.emulate_cycle();
+        assert_eq!(chip8.pc, 0x0ABC);
+    }
+
+    #[test]
+    fn op_2nnn_calls_subroutine_and_pushes_return_address() {
+        let mut chip8 = with_opcode(0x2ABC);
+        chip8.emulate_cycle();
+        assert_eq!(chip8.pc, 0x0ABC);
+        assert_eq!(chip8.sp, 1);
+        assert_eq!(chip8.stack[0], PROGRAM_START_ADDRESS);
+    }
+
+    #[test]
+    fn op_3xnn_skips_when_equal() {
+        let mut chip8 = with_opcode(0x3A11);
+        chip8.V[0xA] = 0x11;
+        chip8.emulate_cycle();
+        assert_eq!(chip8.pc, PROGRAM_START_ADDRESS + 4);
+    }
+
+    #[test]
+    fn op_4xnn_does_not_skip_when_equal() {
+        let mut chip8 = with_opcode(0x4A11);
+        chip8.V[0xA] = 0x11;
+        chip8.emulate_cycle();
+        assert_eq!(chip8.pc, PROGRAM_START_ADDRESS + 2);
+    }
+
+    #[test]
+    fn op_5xy0_skips_when_registers_equal() {
+        let mut chip8 = with_opcode(0x5AB0);
+        chip8.V[0xA] = 7;
+        chip8.V[0xB] = 7;
+        chip8.emulate_cycle();
+        assert_eq!(chip8.pc, PROGRAM_START_ADDRESS + 4);
+    }
+
+    #[test]
+    fn op_9xy0_skips_when_registers_differ() {
+        let mut chip8 = with_opcode(0x9AB0);
+        chip8.V[0xA] = 7;
+        chip8.V[0xB] = 8;
+        chip8.emulate_cycle();
+        assert_eq!(chip8.pc, PROGRAM_START_ADDRESS + 4);
+    }
+
+    #[test]
+    fn op_6xnn_sets_register() {
+        let mut chip8 = with_opcode(0x600A);
+        chip8.emulate_cycle();
+        assert_eq!(chip8.V[0], 0x0A);
+    }
+
+    #[test]
+    fn op_7xnn_adds_without_setting_carry() {
+        let mut chip8 = with_opcode(0x70FF);
+        chip8.V[0] = 0x02;
+        chip8.emulate_cycle();
+        assert_eq!(chip8.V[0], 0x01); // wraps, VF untouched
+        assert_eq!(chip8.V[0xF], 0);
+    }
+
+    #[test]
+    fn op_8xy4_adds_with_carry_flag() {
+        let mut chip8 = with_opcode(0x8014);
+        chip8.V[0] = 0xFF;
+        chip8.V[1] = 0x02;
+        chip8.emulate_cycle();
+        assert_eq!(chip8.V[0], 0x01);
+        assert_eq!(chip8.V[0xF], 1);
+    }
+
+    #[test]
+    fn op_8xy5_subtracts_with_borrow_flag() {
+        let mut chip8 = with_opcode(0x8015);
+        chip8.V[0] = 0x01;
+        chip8.V[1] = 0x02;
+        chip8.emulate_cycle();
+        assert_eq!(chip8.V[0], 0xFF);
+        assert_eq!(chip8.V[0xF], 0); // borrow occurred
+    }
+
+    #[test]
+    fn op_8xy6_shifts_right_into_vf() {
+        let mut chip8 = with_opcode(0x8016);
+        chip8.V[0] = 0x03;
+        chip8.emulate_cycle();
+        assert_eq!(chip8.V[0], 0x01);
+        assert_eq!(chip8.V[0xF], 1);
+    }
+
+    #[test]
+    fn op_8xye_shifts_left_into_vf() {
+        let mut chip8 = with_opcode(0x801E);
+        chip8.V[0] = 0x81;
+        chip8.emulate_cycle();
+        assert_eq!(chip8.V[0], 0x02);
+        assert_eq!(chip8.V[0xF], 1);
+    }
+
+    #[test]
+    fn op_annn_sets_index_register_to_a_full_12_bit_address() {
+        let mut chip8 = with_opcode(0xAFFF);
+        chip8.emulate_cycle();
+        assert_eq!(chip8.index_register, 0x0FFF);
+    }
+
+    #[test]
+    fn op_cxnn_masks_the_random_byte_with_nn() {
+        let mut chip8 = with_opcode(0xC000); // NN = 0 masks every random byte to 0
+        chip8.emulate_cycle();
+        assert_eq!(chip8.V[0], 0);
+    }
+
+    #[test]
+    fn with_seed_produces_a_reproducible_cxnn_stream() {
+        let mut a = Chip8::with_seed(42);
+        a.pc = PROGRAM_START_ADDRESS;
+        a.memory[PROGRAM_START_ADDRESS as usize] = 0xC0;
+        a.memory[PROGRAM_START_ADDRESS as usize + 1] = 0xFF;
+        a.emulate_cycle();
+
+        let mut b = Chip8::with_seed(42);
+        b.pc = PROGRAM_START_ADDRESS;
+        b.memory[PROGRAM_START_ADDRESS as usize] = 0xC0;
+        b.memory[PROGRAM_START_ADDRESS as usize + 1] = 0xFF;
+        b.emulate_cycle();
+
+        assert_eq!(a.V[0], b.V[0]);
+    }
+
+    #[test]
+    fn op_bnnn_jumps_to_nnn_plus_v0() {
+        let mut chip8 = with_opcode(0xB100);
+        chip8.V[0] = 0x05;
+        chip8.emulate_cycle();
+        assert_eq!(chip8.pc, 0x105);
+    }
+
+    #[test]
+    fn op_ex9e_skips_when_key_pressed() {
+        let mut chip8 = with_opcode(0xE09E);
+        chip8.V[0] = 0x5;
+        chip8.keypad[0x5] = 1;
+        chip8.emulate_cycle();
+        assert_eq!(chip8.pc, PROGRAM_START_ADDRESS + 4);
+    }
+
+    #[test]
+    fn op_exa1_skips_when_key_not_pressed() {
+        let mut chip8 = with_opcode(0xE0A1);
+        chip8.V[0] = 0x5;
+        chip8.emulate_cycle();
+        assert_eq!(chip8.pc, PROGRAM_START_ADDRESS + 4);
+    }
+
+    #[test]
+    fn op_fx07_reads_delay_timer() {
+        let mut chip8 = with_opcode(0xF007);
+        chip8.delay_timer = 9;
+        chip8.emulate_cycle();
+        assert_eq!(chip8.V[0], 9);
+    }
+
+    #[test]
+    fn handle_timers_ticks_both_timers_down_by_one() {
+        let mut chip8 = Chip8::new();
+        chip8.delay_timer = 2;
+        chip8.sound_timer = 2;
+        chip8.handle_timers();
+        assert_eq!(chip8.delay_timer, 1);
+        assert_eq!(chip8.sound_timer, 1);
+    }
+
+    #[test]
+    fn handle_timers_does_not_run_per_opcode() {
+        let mut chip8 = with_opcode(0x1200); // JP 0x200: an unrelated opcode
+        chip8.delay_timer = 5;
+        chip8.emulate_cycle();
+        assert_eq!(chip8.delay_timer, 5);
+    }
+
+    #[test]
+    fn op_fx15_sets_delay_timer() {
+        let mut chip8 = with_opcode(0xF015);
+        chip8.V[0] = 9;
+        chip8.emulate_cycle();
+        assert_eq!(chip8.delay_timer, 9); // timers no longer tick per opcode
+    }
+
+    #[test]
+    fn op_fx1e_adds_vx_to_index_register() {
+        let mut chip8 = with_opcode(0xF01E);
+        chip8.index_register = 0x10;
+        chip8.V[0] = 0x05;
+        chip8.emulate_cycle();
+        assert_eq!(chip8.index_register, 0x15);
+    }
+
+    #[test]
+    fn op_fx1e_wraps_index_register_back_into_the_12_bit_address_space() {
+        let mut chip8 = with_opcode(0xF01E);
+        chip8.index_register = 0x0FFF;
+        chip8.V[0] = 0xFF;
+        chip8.emulate_cycle();
+        assert_eq!(chip8.index_register, 0x00FE);
+    }
+
+    #[test]
+    fn op_fx33_stores_bcd_of_vx() {
+        let mut chip8 = with_opcode(0xF033);
+        chip8.index_register = 0x300;
+        chip8.V[0] = 123;
+        chip8.emulate_cycle();
+        assert_eq!(chip8.memory[0x300], 1);
+        assert_eq!(chip8.memory[0x301], 2);
+        assert_eq!(chip8.memory[0x302], 3);
+    }
+
+    #[test]
+    fn op_fx55_and_fx65_roundtrip_registers_through_memory() {
+        let mut chip8 = with_opcode(0xF255);
+        chip8.index_register = 0x300;
+        chip8.V[0] = 1;
+        chip8.V[1] = 2;
+        chip8.V[2] = 3;
+        chip8.emulate_cycle();
+        assert_eq!(chip8.memory[0x300..0x303], [1, 2, 3]);
+
+        let mut chip8 = with_opcode(0xF265);
+        chip8.index_register = 0x300;
+        chip8.memory[0x300] = 9;
+        chip8.memory[0x301] = 8;
+        chip8.memory[0x302] = 7;
+        chip8.emulate_cycle();
+        assert_eq!([chip8.V[0], chip8.V[1], chip8.V[2]], [9, 8, 7]);
+    }
+
+    #[test]
+    fn op_dxyn_draws_sprite_and_sets_collision_flag() {
+        let mut chip8 = with_opcode(0xD011);
+        chip8.index_register = 0x300;
+        chip8.memory[0x300] = 0x80; // single lit pixel in the top-left corner
+        chip8.gfx[0] = 1; // already lit, so drawing XORs it off -> collision
+        chip8.emulate_cycle();
+        assert_eq!(chip8.gfx[0], 0);
+        assert_eq!(chip8.V[0xF], 1);
+        assert!(chip8.draw_flag);
+    }
+
+    #[test]
+    fn load_fontset_in_memory_copies_the_font_into_the_start_of_memory() {
+        let mut chip8 = Chip8::new();
+        chip8.load_fontset_in_memory();
+        assert_eq!(&chip8.memory[..FONT_SET.len()], &FONT_SET[..]);
+    }
+
+    #[test]
+    fn op_fx29_points_i_at_the_requested_glyph() {
+        let mut chip8 = with_opcode(0xF329);
+        chip8.V[3] = 0x4; // digit 4
+        chip8.emulate_cycle();
+        assert_eq!(chip8.index_register, 4 * 5);
+    }
+
+    #[test]
+    fn dxyn_draws_the_loaded_font_glyph_for_zero() {
+        let mut chip8 = with_opcode(0xD015); // DRW V0, V1, 5
+        chip8.load_fontset_in_memory();
+        chip8.index_register = 0; // glyph '0' lives at the start of the font table
+        chip8.V[0] = 0;
+        chip8.emulate_cycle();
+
+        // Glyph '0' is 0xF0,0x90,0x90,0x90,0xF0 read MSB-first, 8 columns wide.
+        let rows: [u8; 5] = [0xF0, 0x90, 0x90, 0x90, 0xF0];
+        for (row, &byte) in rows.iter().enumerate() {
+            for col in 0..8 {
+                let expected = (byte >> (7 - col)) & 1;
+                assert_eq!(
+                    chip8.gfx[row * 64 + col as usize],
+                    expected,
+                    "row {} col {}",
+                    row,
+                    col
+                );
+            }
+        }
+        assert_eq!(chip8.V[0xF], 0); // nothing was on screen before, so no collision
+        assert!(chip8.draw_flag);
+    }
+
+    #[test]
+    fn load_program_bytes_copies_the_rom_to_program_start() {
+        let mut chip8 = Chip8::new();
+        chip8.load_program_bytes(&[0x00, 0xE0, 0x12, 0x34]);
+        let start = PROGRAM_START_ADDRESS as usize;
+        assert_eq!(&chip8.memory[start..start + 4], &[0x00, 0xE0, 0x12, 0x34]);
+    }
+
+    #[test]
+    fn frame_buffer_exposes_the_gfx_buffer() {
+        let mut chip8 = Chip8::new();
+        chip8.gfx[5] = 1;
+        assert_eq!(chip8.frame_buffer()[5], 1);
+    }
+
+    #[test]
+    fn tick_runs_one_cycle_and_reports_the_draw_flag() {
+        let mut chip8 = with_opcode(0x00E0); // CLS: sets draw_flag
+        assert!(chip8.tick());
+        assert!(!chip8.draw_flag); // tick clears it after reporting
+
+        let mut chip8 = with_opcode(0x1200); // JP 0x200: does not touch gfx
+        assert!(!chip8.tick());
+    }
+
+    #[test]
+    fn set_key_updates_the_keypad_entry() {
+        let mut chip8 = Chip8::new();
+        chip8.set_key(0xA, true);
+        assert_eq!(chip8.keypad[0xA], 1);
+        chip8.set_key(0xA, false);
+        assert_eq!(chip8.keypad[0xA], 0);
+    }
+
+    #[test]
+    fn set_key_ignores_out_of_range_keys_instead_of_panicking() {
+        let mut chip8 = Chip8::new();
+        chip8.set_key(0x10, true);
+        chip8.set_key(0xFF, true);
+        assert!(chip8.keypad.iter().all(|&pressed| pressed == 0));
     }
 }
\ No newline at end of file
@@ -0,0 +1,142 @@
+//! Rendering and input backends for the CHIP-8 core.
+//!
+//! The core `Chip8` decode loop stays backend-agnostic: it only ever talks to
+//! a `Frontend` trait object, so a new display/input backend can be plugged
+//! in without touching `cpu.rs`.
+
+use failure::Error;
+use sdl2::event::Event;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use sdl2::EventPump;
+
+/// Logical display dimensions of the CHIP-8 screen.
+const DISPLAY_WIDTH: u32 = 64;
+const DISPLAY_HEIGHT: u32 = 32;
+
+/// A display/input backend driven by the `Chip8` core.
+pub trait Frontend {
+    /// Draw the 64x32 `gfx` buffer (one byte per pixel, 0 or 1) to the screen.
+    fn present(&mut self, gfx: &[u8]);
+
+    /// Pump pending input events into `keypad` (1 = pressed, 0 = released).
+    /// Returns `false` when the user requested to quit.
+    fn poll_input(&mut self, keypad: &mut [u8]) -> bool;
+
+    /// Play the CHIP-8 beep.
+    fn beep(&mut self);
+}
+
+/// An SDL2-backed `Frontend`, scaling the 64x32 logical surface up by `scale`.
+pub struct SdlFrontend {
+    canvas: Canvas<Window>,
+    event_pump: EventPump,
+    scale: u32,
+}
+
+impl SdlFrontend {
+    /// Open a window scaled up from the logical 64x32 CHIP-8 surface.
+    pub fn new(scale: u32) -> Result<SdlFrontend, Error> {
+        let sdl_context = sdl2::init().map_err(|e| format_err!("sdl2 init failed: {}", e))?;
+        let video_subsystem = sdl_context
+            .video()
+            .map_err(|e| format_err!("sdl2 video init failed: {}", e))?;
+
+        let window = video_subsystem
+            .window(
+                "Rusty CHIP-8",
+                DISPLAY_WIDTH * scale,
+                DISPLAY_HEIGHT * scale,
+            )
+            .position_centered()
+            .build()?;
+
+        let canvas = window.into_canvas().build()?;
+        let event_pump = sdl_context
+            .event_pump()
+            .map_err(|e| format_err!("sdl2 event pump init failed: {}", e))?;
+
+        Ok(SdlFrontend {
+            canvas,
+            event_pump,
+            scale,
+        })
+    }
+}
+
+impl Frontend for SdlFrontend {
+    fn present(&mut self, gfx: &[u8]) {
+        self.canvas.set_draw_color(Color::RGB(0, 0, 0));
+        self.canvas.clear();
+
+        self.canvas.set_draw_color(Color::RGB(255, 255, 255));
+        for (i, &pixel) in gfx.iter().enumerate() {
+            if pixel == 0 {
+                continue;
+            }
+            let x = (i as u32 % DISPLAY_WIDTH) * self.scale;
+            let y = (i as u32 / DISPLAY_WIDTH) * self.scale;
+            let _ = self
+                .canvas
+                .fill_rect(Rect::new(x as i32, y as i32, self.scale, self.scale));
+        }
+
+        self.canvas.present();
+    }
+
+    fn poll_input(&mut self, keypad: &mut [u8]) -> bool {
+        for event in self.event_pump.poll_iter() {
+            match event {
+                Event::Quit { .. } => return false,
+                Event::KeyDown {
+                    keycode: Some(keycode),
+                    ..
+                } => {
+                    if let Some(key) = map_keycode(keycode) {
+                        keypad[key] = 1;
+                    }
+                }
+                Event::KeyUp {
+                    keycode: Some(keycode),
+                    ..
+                } => {
+                    if let Some(key) = map_keycode(keycode) {
+                        keypad[key] = 0;
+                    }
+                }
+                _ => {}
+            }
+        }
+        true
+    }
+
+    fn beep(&mut self) {
+        println!("BEEP!");
+    }
+}
+
+/// Maps the classic 1234/QWER/ASDF/ZXCV layout onto the 16-key CHIP-8 keypad.
+fn map_keycode(keycode: Keycode) -> Option<usize> {
+    match keycode {
+        Keycode::Num1 => Some(0x1),
+        Keycode::Num2 => Some(0x2),
+        Keycode::Num3 => Some(0x3),
+        Keycode::Num4 => Some(0xC),
+        Keycode::Q => Some(0x4),
+        Keycode::W => Some(0x5),
+        Keycode::E => Some(0x6),
+        Keycode::R => Some(0xD),
+        Keycode::A => Some(0x7),
+        Keycode::S => Some(0x8),
+        Keycode::D => Some(0x9),
+        Keycode::F => Some(0xE),
+        Keycode::Z => Some(0xA),
+        Keycode::X => Some(0x0),
+        Keycode::C => Some(0xB),
+        Keycode::V => Some(0xF),
+        _ => None,
+    }
+}
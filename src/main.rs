@@ -1,16 +1,31 @@
 
-#![feature(fs_read_write)] //TO REMOVE with ugraded nightly or stable
-
 #[macro_use] extern crate failure;
+extern crate rand;
+#[cfg(feature = "native")]
+extern crate sdl2;
 
+pub mod asm;
 pub mod cpu;
+#[cfg(feature = "native")]
+pub mod frontend;
+pub mod rng;
 
+#[cfg(feature = "native")]
 use cpu::Chip8;
+#[cfg(feature = "native")]
+use failure::Error;
 
-fn main() {
+#[cfg(feature = "native")]
+fn main() -> Result<(), Error> {
     println!("Rusty CHIP 8 Emulator");
 
     let mut emulator = Chip8::new();
-    emulator.boot("placeholderfileame.rom");
-    emulator.run();
+    emulator.boot("placeholderfileame.rom")?;
+    emulator.run()?;
+    Ok(())
+}
+
+#[cfg(not(feature = "native"))]
+fn main() {
+    println!("Build with --features native to run the SDL2 front-end.");
 }
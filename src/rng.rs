@@ -0,0 +1,52 @@
+//! An injectable byte source for the CXNN opcode.
+//!
+//! The core never calls the global RNG directly so it stays testable: under
+//! `native` the default backend draws from the thread-local RNG, while
+//! `Chip8::with_seed` installs a seeded PRNG that produces a reproducible byte
+//! stream, letting a whole ROM run be replayed deterministically for
+//! golden-trace regression tests. `ThreadRngSource` is gated out of headless
+//! (e.g. `wasm32`) builds: `rand`'s thread-local RNG pulls in `getrandom`,
+//! which needs extra per-target feature wiring to get OS entropy in a
+//! browser, so a headless host should call `Chip8::with_seed` with a seed it
+//! sources itself (e.g. from JS `crypto.getRandomValues`) instead.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// A source of random bytes for the CXNN opcode.
+pub trait RngSource {
+    fn next_u8(&mut self) -> u8;
+}
+
+/// The default native backend: draws from the thread-local RNG.
+#[cfg(feature = "native")]
+pub struct ThreadRngSource(rand::rngs::ThreadRng);
+
+#[cfg(feature = "native")]
+impl ThreadRngSource {
+    pub fn new() -> ThreadRngSource {
+        ThreadRngSource(rand::thread_rng())
+    }
+}
+
+#[cfg(feature = "native")]
+impl RngSource for ThreadRngSource {
+    fn next_u8(&mut self) -> u8 {
+        self.0.gen()
+    }
+}
+
+/// A seeded backend producing a reproducible byte stream.
+pub struct SeededRngSource(StdRng);
+
+impl SeededRngSource {
+    pub fn new(seed: u64) -> SeededRngSource {
+        SeededRngSource(StdRng::seed_from_u64(seed))
+    }
+}
+
+impl RngSource for SeededRngSource {
+    fn next_u8(&mut self) -> u8 {
+        self.0.gen()
+    }
+}